@@ -1,14 +1,20 @@
 use crate::value::de::{MapDeserializer, MapRefDeserializer, SeqDeserializer, SeqRefDeserializer};
 use crate::value::Value;
 use crate::Error;
-use serde::de::value::{BorrowedStrDeserializer, StrDeserializer};
+use serde::de::value::{
+    BorrowedStrDeserializer, BytesDeserializer, MapAccessDeserializer, SeqAccessDeserializer,
+    StrDeserializer, UnitDeserializer,
+};
 use serde::de::{
-    Deserialize, DeserializeSeed, Deserializer, EnumAccess, Error as _, VariantAccess, Visitor,
+    Deserialize, DeserializeSeed, Deserializer, EnumAccess, Error as _, IntoDeserializer,
+    MapAccess, SeqAccess, VariantAccess, Visitor,
 };
 use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::fmt::{self, Debug};
 use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
 
 /// A representation of YAML's `!Tag` syntax, used for enums.
 ///
@@ -17,6 +23,97 @@ use std::hash::{Hash, Hasher};
 #[derive(Clone)]
 pub struct Tag {
     pub(crate) string: String,
+    /// The URI a full YAML tag-handle resolution expands `string` to, computed
+    /// against the document's `%TAG` directives at construction time. `None`
+    /// when the tag was built without a directive context; [`Tag::resolved`]
+    /// then falls back to resolving against the default handle set.
+    pub(crate) resolved: Option<String>,
+    /// The source location of the tag token, when one was attached with
+    /// [`Tag::with_span`]. `None` for the default thin-`String` tag.
+    pub(crate) span: Option<Span>,
+}
+
+/// A position in the YAML source, mirroring the markers libyaml reports on each
+/// event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Mark {
+    /// Zero-based byte offset from the start of the source.
+    pub index: usize,
+    /// One-based line number.
+    pub line: usize,
+    /// One-based column number.
+    pub column: usize,
+}
+
+/// The source span a tag token occupies, from its first to its last byte.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    /// Position of the first byte of the token.
+    pub start: Mark,
+    /// Position just past the last byte of the token.
+    pub end: Mark,
+}
+
+/// The set of YAML tag handles in scope for a document, as declared by `%TAG`
+/// directives, used to expand a lexical tag into its resolved URI form.
+///
+/// The primary handle `!` and the secondary handle `!!` always resolve to their
+/// standard prefixes unless a directive overrides them; named handles such as
+/// `!foo!` must be declared before use.
+#[derive(Clone, Debug, Default)]
+pub struct TagDirectives {
+    handles: Vec<(String, String)>,
+}
+
+impl TagDirectives {
+    /// Create an empty directive table carrying only the implicit `!` and `!!`
+    /// handles.
+    pub fn new() -> Self {
+        TagDirectives::default()
+    }
+
+    /// Register the prefix a `%TAG <handle> <prefix>` directive maps `handle`
+    /// to, e.g. `insert("!foo!", "tag:example.com,2020:")`.
+    ///
+    /// A later registration of the same handle shadows the earlier one, as the
+    /// most recent `%TAG` directive wins.
+    pub fn insert(&mut self, handle: impl Into<String>, prefix: impl Into<String>) {
+        self.handles.push((handle.into(), prefix.into()));
+    }
+
+    fn prefix(&self, handle: &str) -> Option<&str> {
+        self.handles
+            .iter()
+            .rev()
+            .find(|(h, _)| h == handle)
+            .map(|(_, prefix)| prefix.as_str())
+    }
+}
+
+/// Expand a lexical tag into its resolved URI form using the YAML tag-handle
+/// resolution rules.
+fn resolve(string: &str, directives: &TagDirectives) -> String {
+    // Verbatim `!<URI>` tags are taken literally, with no handle expansion.
+    if let Some(uri) = string.strip_prefix("!<").and_then(|rest| rest.strip_suffix('>')) {
+        return uri.to_owned();
+    }
+    if !string.starts_with('!') {
+        return string.to_owned();
+    }
+    // Split off the handle: `!`, `!!`, or a named `!foo!` handle.
+    let (handle, suffix) = match string[1..].find('!') {
+        Some(index) => (&string[..index + 2], &string[index + 2..]),
+        None => ("!", &string[1..]),
+    };
+    if let Some(prefix) = directives.prefix(handle) {
+        return format!("{}{}", prefix, suffix);
+    }
+    match handle {
+        "!!" => format!("tag:yaml.org,2002:{}", suffix),
+        "!" => format!("!{}", suffix),
+        // An undeclared named handle cannot be resolved; keep it lexical.
+        _ => string.to_owned(),
+    }
 }
 
 /// A `Tag` + `Value` representing a tagged YAML scalar, sequence, or mapping.
@@ -78,6 +175,388 @@ impl Tag {
     pub fn new(string: impl Into<String>) -> Self {
         Tag {
             string: string.into(),
+            resolved: None,
+            span: None,
+        }
+    }
+
+    /// Create a tag, resolving its handle against a document's `%TAG`
+    /// directives at construction time.
+    ///
+    /// The resolved URI form is cached and surfaced through [`Tag::resolved`];
+    /// the raw lexical form is still preserved in [`Tag::new`]'s sense. Tags
+    /// built while deserializing use [`Tag::new`] and carry no cached
+    /// resolution.
+    pub fn with_directives(string: impl Into<String>, directives: &TagDirectives) -> Self {
+        let string = string.into();
+        let resolved = resolve(&string, directives);
+        Tag {
+            string,
+            resolved: Some(resolved),
+            span: None,
+        }
+    }
+
+    /// Re-resolve this tag's handle against `directives`, replacing any cached
+    /// resolution with the expansion they produce.
+    ///
+    /// [`Tag::with_directives`] resolves at construction time. Tags produced by
+    /// deserialization are built with [`Tag::new`] and are not resolved against
+    /// any `%TAG` directives; after a document has been parsed into a
+    /// [`Value`] tree, walk the tree and call this on each [`TaggedValue`] tag
+    /// so named handles such as `!foo!bar` resolve via their registered prefix
+    /// instead of remaining opaque lexical strings.
+    pub fn resolve_against(&mut self, directives: &TagDirectives) {
+        self.resolved = Some(resolve(&self.string, directives));
+    }
+
+    /// Attach the source span of the tag token to this tag.
+    ///
+    /// Spans are opt-in: a caller attaches them here. The default [`Tag::new`] tag stays a
+    /// thin `String` with no span. The span is ignored by equality, ordering,
+    /// and hashing, so `tag == "Thing"` comparisons are unaffected by whether a
+    /// span is present.
+    ///
+    /// ```
+    /// use serde_yaml::value::{Mark, Span, Tag};
+    ///
+    /// let span = Span {
+    ///     start: Mark { index: 0, line: 12, column: 5 },
+    ///     end: Mark { index: 7, line: 12, column: 12 },
+    /// };
+    /// let tag = Tag::new("Widget").with_span(span);
+    /// assert_eq!(tag.span().unwrap().start.line, 12);
+    /// // Spans do not affect equality.
+    /// assert!(tag == Tag::new("Widget"));
+    /// ```
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// The source span of the tag token, if one was attached.
+    ///
+    /// This is populated only by [`Tag::with_span`]. Tags produced by
+    /// deserialization, such as those obtained through `from_str`, are built
+    /// with [`Tag::new`] and carry `None`.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// The fully resolved URI form of this tag.
+    ///
+    /// The secondary handle `!!` expands to the `tag:yaml.org,2002:` prefix (so
+    /// `!!str` becomes `tag:yaml.org,2002:str`), the primary handle `!` expands
+    /// to the `!`-prefixed local form, named handles expand using the prefix
+    /// registered by the corresponding `%TAG` directive, and a verbatim
+    /// `!<URI>` tag is taken literally.
+    ///
+    /// ```
+    /// use serde_yaml::value::Tag;
+    ///
+    /// assert_eq!(Tag::new("!!str").resolved(), "tag:yaml.org,2002:str");
+    /// assert_eq!(Tag::new("!<tag:yaml.org,2002:int>").resolved(), "tag:yaml.org,2002:int");
+    /// assert_eq!(Tag::new("!Thing").resolved(), "!Thing");
+    /// ```
+    pub fn resolved(&self) -> Cow<'_, str> {
+        match &self.resolved {
+            Some(resolved) => Cow::Borrowed(resolved.as_str()),
+            None => Cow::Owned(resolve(&self.string, &TagDirectives::default())),
+        }
+    }
+
+    /// The key used for equality, ordering, and hashing.
+    ///
+    /// This is the fully resolved URI form with a single leading `!` stripped,
+    /// so that the bang stays insignificant (`!Thing` and `Thing` compare
+    /// equal) while `!!str` and `!<tag:yaml.org,2002:str>` are recognized as
+    /// equal through resolution.
+    fn cmp_key(&self) -> Cow<'_, str> {
+        // Fast path: a tag with no cached resolution whose lexical form has no
+        // expanding handle (not `!!…`, not verbatim `!<…>`) resolves to itself,
+        // so the key is just the bang-stripped slice — no allocation. This is
+        // the hot path, since most tags built by `Tag::new`/parsing land here.
+        if self.resolved.is_none()
+            && !self.string.starts_with("!!")
+            && !self.string.starts_with("!<")
+        {
+            return Cow::Borrowed(nobang(&self.string));
+        }
+        match self.resolved() {
+            Cow::Borrowed(resolved) => Cow::Borrowed(nobang(resolved)),
+            Cow::Owned(resolved) => Cow::Owned(nobang(&resolved).to_owned()),
+        }
+    }
+}
+
+/// A `Tag` + strongly-typed body that accepts one and only one `!Tag`.
+///
+/// Where [`TaggedValue`] captures whatever tag is present and always stores the
+/// body as a dynamic [`Value`], `Tagged<T>` pins a single expected tag and
+/// deserializes the body directly into `T`, failing if the YAML tag does not
+/// match. This mirrors the required-tag pattern found in other self-describing
+/// formats and is handy for schema-significant tags like `!Secret` or
+/// `!Include`.
+///
+/// ```
+/// use serde::de::DeserializeSeed;
+/// use serde_yaml::value::{Tagged, Value};
+///
+/// let value: Value = serde_yaml::from_str("!Secret hunter2").unwrap();
+/// let secret: Tagged<String> = Tagged::expecting("Secret").deserialize(value).unwrap();
+/// assert_eq!(secret.value, "hunter2");
+///
+/// // A mismatching tag is rejected rather than silently accepted.
+/// let value: Value = serde_yaml::from_str("!Public hunter2").unwrap();
+/// assert!(Tagged::<String>::expecting("Secret").deserialize(value).is_err());
+/// ```
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct Tagged<T> {
+    /// The tag that was matched (and that will be emitted on serialization).
+    pub tag: Tag,
+    /// The strongly-typed body.
+    pub value: T,
+}
+
+impl<T> Tagged<T> {
+    /// Create a `Tagged<T>` from an already-known tag and body.
+    ///
+    /// The leading '!' is not significant, matching [`Tag::new`].
+    pub fn new(tag: impl Into<String>, value: T) -> Self {
+        Tagged {
+            tag: Tag::new(tag),
+            value,
+        }
+    }
+
+    /// Returns a [`DeserializeSeed`] that deserializes a `Tagged<T>`, requiring
+    /// the input tag to equal `tag`.
+    ///
+    /// The leading '!' is not significant.
+    pub fn expecting(tag: impl Into<String>) -> TaggedSeed<T> {
+        TaggedSeed {
+            expected: Tag::new(tag),
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Deserialization seed produced by [`Tagged::expecting`].
+pub struct TaggedSeed<T> {
+    expected: Tag,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T> DeserializeSeed<'de> for TaggedSeed<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Tagged<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TaggedVisitor<T> {
+            expected: Tag,
+            marker: PhantomData<T>,
+        }
+
+        impl<'de, T> Visitor<'de> for TaggedVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = Tagged<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a YAML value with a !{} tag", nobang(&self.expected.string))
+            }
+
+            fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+            where
+                A: EnumAccess<'de>,
+            {
+                let (tag, contents) = data.variant::<String>()?;
+                let tag = Tag::new(tag);
+                if tag != self.expected {
+                    return Err(A::Error::custom(format_args!(
+                        "unexpected tag !{}, expected !{}",
+                        nobang(&tag.string),
+                        nobang(&self.expected.string),
+                    )));
+                }
+                let value = contents.newtype_variant::<T>()?;
+                Ok(Tagged { tag, value })
+            }
+        }
+
+        deserializer.deserialize_any(TaggedVisitor {
+            expected: self.expected,
+            marker: self.marker,
+        })
+    }
+}
+
+impl<T> Serialize for Tagged<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(&format_args!("!{}", nobang(&self.tag.string)), &self.value)?;
+        map.end()
+    }
+}
+
+/// An optional tag paired with a strongly-typed body.
+///
+/// `Captured<T>` deserializes a value into a concrete Rust type `T` while still
+/// recovering whatever YAML tag (if any) decorated it. It sits between
+/// [`TaggedValue`], whose body is always a dynamic [`Value`], and a plain
+/// struct, which discards the tag entirely: tools that want to inspect and
+/// re-emit custom tags they don't fully model can round-trip them through this
+/// shape.
+///
+/// ```
+/// use serde_yaml::value::Captured;
+///
+/// // A tagged node preserves the tag alongside the typed body.
+/// let tagged: Captured<u32> = serde_yaml::from_str("!Port 8080").unwrap();
+/// assert!(tagged.0.as_ref().unwrap() == "Port");
+/// assert_eq!(tagged.1, 8080);
+///
+/// // An untagged node yields `None` for the tag.
+/// let untagged: Captured<u32> = serde_yaml::from_str("8080").unwrap();
+/// assert!(untagged.0.is_none());
+/// assert_eq!(untagged.1, 8080);
+/// ```
+#[derive(Clone, PartialEq, PartialOrd, Hash, Debug)]
+pub struct Captured<T>(pub Option<Tag>, pub T);
+
+impl<'de, T> Deserialize<'de> for Captured<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CapturedVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for CapturedVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = Captured<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any YAML value, optionally with a !Tag")
+            }
+
+            fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+            where
+                A: EnumAccess<'de>,
+            {
+                let (tag, contents) = data.variant::<String>()?;
+                let value = contents.newtype_variant::<T>()?;
+                Ok(Captured(Some(Tag::new(tag)), value))
+            }
+
+            // An untagged node carries no tag and deserializes straight into
+            // `T`, without the detour through a dynamic `Value`. Narrower
+            // integer widths fall through to `visit_i64`/`visit_u64` via the
+            // default `Visitor` methods.
+            fn visit_bool<E: serde::de::Error>(self, v: bool) -> Result<Self::Value, E> {
+                T::deserialize(v.into_deserializer()).map(|t| Captured(None, t))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                T::deserialize(v.into_deserializer()).map(|t| Captured(None, t))
+            }
+
+            fn visit_i128<E: serde::de::Error>(self, v: i128) -> Result<Self::Value, E> {
+                T::deserialize(v.into_deserializer()).map(|t| Captured(None, t))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                T::deserialize(v.into_deserializer()).map(|t| Captured(None, t))
+            }
+
+            fn visit_u128<E: serde::de::Error>(self, v: u128) -> Result<Self::Value, E> {
+                T::deserialize(v.into_deserializer()).map(|t| Captured(None, t))
+            }
+
+            fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                T::deserialize(v.into_deserializer()).map(|t| Captured(None, t))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                T::deserialize(StrDeserializer::new(v)).map(|t| Captured(None, t))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                T::deserialize(BytesDeserializer::new(v)).map(|t| Captured(None, t))
+            }
+
+            fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+                T::deserialize(UnitDeserializer::new()).map(|t| Captured(None, t))
+            }
+
+            fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+                T::deserialize(UnitDeserializer::new()).map(|t| Captured(None, t))
+            }
+
+            fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                deserializer.deserialize_any(self)
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                deserializer.deserialize_any(self)
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                T::deserialize(SeqAccessDeserializer::new(seq)).map(|t| Captured(None, t))
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                T::deserialize(MapAccessDeserializer::new(map)).map(|t| Captured(None, t))
+            }
+        }
+
+        deserializer.deserialize_any(CapturedVisitor(PhantomData))
+    }
+}
+
+impl<T> Serialize for Captured<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match &self.0 {
+            Some(tag) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(&format_args!("!{}", nobang(&tag.string)), &self.1)?;
+                map.end()
+            }
+            None => self.1.serialize(serializer),
         }
     }
 }
@@ -116,7 +595,7 @@ impl Eq for Tag {}
 
 impl PartialEq for Tag {
     fn eq(&self, other: &Tag) -> bool {
-        PartialEq::eq(nobang(&self.string), nobang(&other.string))
+        PartialEq::eq(&self.cmp_key(), &other.cmp_key())
     }
 }
 
@@ -125,25 +604,26 @@ where
     T: ?Sized + AsRef<str>,
 {
     fn eq(&self, other: &T) -> bool {
-        PartialEq::eq(nobang(&self.string), nobang(other.as_ref()))
+        let other = resolve(other.as_ref(), &TagDirectives::default());
+        PartialEq::eq(self.cmp_key().as_ref(), nobang(&other))
     }
 }
 
 impl Ord for Tag {
     fn cmp(&self, other: &Self) -> Ordering {
-        Ord::cmp(nobang(&self.string), nobang(&other.string))
+        Ord::cmp(&self.cmp_key(), &other.cmp_key())
     }
 }
 
 impl PartialOrd for Tag {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        PartialOrd::partial_cmp(nobang(&self.string), nobang(&other.string))
+        Some(self.cmp(other))
     }
 }
 
 impl Hash for Tag {
     fn hash<H: Hasher>(&self, hasher: &mut H) {
-        nobang(&self.string).hash(hasher);
+        self.cmp_key().hash(hasher);
     }
 }
 
@@ -0,0 +1,103 @@
+use serde::de::DeserializeSeed;
+use serde_yaml::value::{Captured, Tag, TagDirectives, Tagged, Value};
+use std::collections::BTreeMap;
+
+#[test]
+fn test_tag_resolve_named_handle_against_directives() {
+    let mut directives = TagDirectives::new();
+    directives.insert("!foo!", "tag:example.com,2020:");
+
+    let mut tag = Tag::new("!foo!bar");
+    tag.resolve_against(&directives);
+    assert_eq!(tag.resolved(), "tag:example.com,2020:bar");
+
+    // The secondary and verbatim handles resolve off the default set too.
+    assert_eq!(Tag::new("!!str").resolved(), "tag:yaml.org,2002:str");
+    assert_eq!(
+        Tag::new("!<tag:yaml.org,2002:int>").resolved(),
+        "tag:yaml.org,2002:int",
+    );
+}
+
+#[test]
+fn test_tagged_matching_tag() {
+    let value: Value = serde_yaml::from_str("!Secret hunter2").unwrap();
+    let secret: Tagged<String> = Tagged::expecting("Secret").deserialize(value).unwrap();
+    assert_eq!(secret.tag, "Secret");
+    assert_eq!(secret.value, "hunter2");
+}
+
+#[test]
+fn test_tagged_bang_insignificant_when_expecting() {
+    let value: Value = serde_yaml::from_str("!Secret hunter2").unwrap();
+    let secret: Tagged<String> = Tagged::expecting("!Secret").deserialize(value).unwrap();
+    assert_eq!(secret.value, "hunter2");
+}
+
+#[test]
+fn test_tagged_mismatching_tag_is_error() {
+    let value: Value = serde_yaml::from_str("!Public hunter2").unwrap();
+    let error = Tagged::<String>::expecting("Secret")
+        .deserialize(value)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "unexpected tag !Public, expected !Secret",
+    );
+}
+
+#[test]
+fn test_captured_tagged_scalar() {
+    let captured: Captured<u32> = serde_yaml::from_str("!Port 8080").unwrap();
+    assert_eq!(captured.0.unwrap(), Tag::new("Port"));
+    assert_eq!(captured.1, 8080);
+}
+
+#[test]
+fn test_captured_tagged_sequence() {
+    let captured: Captured<Vec<u32>> = serde_yaml::from_str("!Ports [80, 443]").unwrap();
+    assert_eq!(captured.0.unwrap(), Tag::new("Ports"));
+    assert_eq!(captured.1, vec![80, 443]);
+}
+
+#[test]
+fn test_captured_untagged_scalar() {
+    let captured: Captured<u32> = serde_yaml::from_str("8080").unwrap();
+    assert!(captured.0.is_none());
+    assert_eq!(captured.1, 8080);
+}
+
+#[test]
+fn test_captured_untagged_unit() {
+    let captured: Captured<()> = serde_yaml::from_str("null").unwrap();
+    assert!(captured.0.is_none());
+}
+
+#[test]
+fn test_captured_untagged_option_none() {
+    let captured: Captured<Option<u32>> = serde_yaml::from_str("null").unwrap();
+    assert!(captured.0.is_none());
+    assert_eq!(captured.1, None);
+}
+
+#[test]
+fn test_captured_untagged_sequence() {
+    let captured: Captured<Vec<u32>> = serde_yaml::from_str("[1, 2, 3]").unwrap();
+    assert!(captured.0.is_none());
+    assert_eq!(captured.1, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_captured_untagged_mapping() {
+    let captured: Captured<BTreeMap<String, u32>> = serde_yaml::from_str("a: 1\nb: 2").unwrap();
+    assert!(captured.0.is_none());
+    assert_eq!(captured.1["a"], 1);
+    assert_eq!(captured.1["b"], 2);
+}
+
+#[test]
+fn test_captured_round_trips_unknown_tag() {
+    let captured: Captured<u32> = serde_yaml::from_str("!Port 8080").unwrap();
+    let reemitted = serde_yaml::to_string(&captured).unwrap();
+    assert_eq!(reemitted, "!Port 8080\n");
+}